@@ -272,6 +272,10 @@ impl Inode {
             v
         })
     }
+    /// Size of current inode's data, in bytes
+    pub fn size(&self) -> usize {
+        self.read_disk_inode(|disk_inode| disk_inode.size as usize)
+    }
     /// Read data from current inode
     pub fn read_at(&self, offset: usize, buf: &mut [u8]) -> usize {
         let _fs = self.fs.lock();
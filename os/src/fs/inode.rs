@@ -0,0 +1,169 @@
+//! `OSInode`: a [`File`] wrapping an on-disk `easy-fs` [`Inode`], plus the
+//! `open_file` entry point other syscalls go through to get one.
+
+use super::{File, SeekOrigin};
+use crate::drivers::BLOCK_DEVICE;
+use crate::mm::UserBuffer;
+use crate::sync::UPSafeCell;
+use alloc::sync::Arc;
+use bitflags::bitflags;
+use easy_fs::{EasyFileSystem, Inode};
+use lazy_static::lazy_static;
+
+/// A file backed by an on-disk `easy-fs` inode
+pub struct OSInode {
+    readable: bool,
+    writable: bool,
+    inner: UPSafeCell<OSInodeInner>,
+}
+
+struct OSInodeInner {
+    offset: usize,
+    inode: Arc<Inode>,
+}
+
+impl OSInode {
+    /// Wrap `inode` as a file open for `readable`/`writable` access, cursor at 0
+    pub fn new(readable: bool, writable: bool, inode: Arc<Inode>) -> Self {
+        Self {
+            readable,
+            writable,
+            inner: unsafe { UPSafeCell::new(OSInodeInner { offset: 0, inode }) },
+        }
+    }
+
+}
+
+impl File for OSInode {
+    fn readable(&self) -> bool {
+        self.readable
+    }
+    fn writable(&self) -> bool {
+        self.writable
+    }
+    fn read(&self, mut buf: UserBuffer) -> usize {
+        let mut inner = self.inner.exclusive_access();
+        let mut total_read = 0usize;
+        for slice in buf.buffers.iter_mut() {
+            let len = inner.inode.read_at(inner.offset, slice);
+            if len == 0 {
+                break;
+            }
+            inner.offset += len;
+            total_read += len;
+        }
+        total_read
+    }
+    fn write(&self, buf: UserBuffer) -> usize {
+        let mut inner = self.inner.exclusive_access();
+        let mut total_written = 0usize;
+        for slice in buf.buffers.iter() {
+            let len = inner.inode.write_at(inner.offset, slice);
+            assert_eq!(len, slice.len());
+            inner.offset += len;
+            total_written += len;
+        }
+        total_written
+    }
+    fn get_nlink(&self) -> u32 {
+        self.inner.exclusive_access().inode.get_nlink()
+    }
+    fn seek(&self, offset: i64, whence: SeekOrigin) -> isize {
+        let mut inner = self.inner.exclusive_access();
+        let base = match whence {
+            SeekOrigin::Start => 0,
+            SeekOrigin::Cur => inner.offset as i64,
+            SeekOrigin::End => inner.inode.size() as i64,
+        };
+        let new_offset = base + offset;
+        if new_offset < 0 {
+            return -1;
+        }
+        inner.offset = new_offset as usize;
+        new_offset as isize
+    }
+}
+
+bitflags! {
+    /// Flags accepted by `sys_open`, matching POSIX `open(2)`'s low bits
+    pub struct OpenFlags: u32 {
+        /// open read-only
+        const RDONLY = 0;
+        /// open write-only
+        const WRONLY = 1 << 0;
+        /// open read-write
+        const RDWR = 1 << 1;
+        /// create the file if it does not exist
+        const CREATE = 1 << 9;
+        /// truncate an existing file to length 0
+        const TRUNC = 1 << 10;
+    }
+}
+
+impl OpenFlags {
+    /// Decode the `(readable, writable)` pair implied by the `RDONLY`/`WRONLY`/`RDWR` bits
+    pub fn read_write(&self) -> (bool, bool) {
+        if self.contains(Self::RDWR) {
+            (true, true)
+        } else if self.contains(Self::WRONLY) {
+            (false, true)
+        } else {
+            (true, false)
+        }
+    }
+}
+
+lazy_static! {
+    static ref ROOT_INODE: Arc<Inode> = {
+        let efs = EasyFileSystem::open(BLOCK_DEVICE.clone());
+        Arc::new(EasyFileSystem::root_inode(&efs))
+    };
+}
+
+/// List every app (top-level file) in the root directory
+pub fn list_apps() {
+    println!("/**** APPS ****");
+    for app in ROOT_INODE.ls() {
+        println!("{}", app);
+    }
+    println!("**************/");
+}
+
+/// Open `name`. `/proc/...` paths are served by [`super::open_proc_file`]
+/// rather than going through `easy-fs` at all, since they have no backing
+/// disk inode.
+pub fn open_file(name: &str, flags: OpenFlags) -> Option<Arc<dyn File>> {
+    if name.starts_with("/proc/") {
+        return super::open_proc_file(name).map(|inode| inode as Arc<dyn File>);
+    }
+    let (readable, writable) = flags.read_write();
+    if flags.contains(OpenFlags::CREATE) {
+        if let Some(inode) = ROOT_INODE.find(name) {
+            if flags.contains(OpenFlags::TRUNC) {
+                inode.clear();
+            }
+            Some(Arc::new(OSInode::new(readable, writable, inode)) as Arc<dyn File>)
+        } else {
+            ROOT_INODE
+                .create(name)
+                .map(|inode| Arc::new(OSInode::new(readable, writable, inode)) as Arc<dyn File>)
+        }
+    } else {
+        ROOT_INODE.find(name).map(|inode| {
+            if flags.contains(OpenFlags::TRUNC) {
+                inode.clear();
+            }
+            Arc::new(OSInode::new(readable, writable, inode)) as Arc<dyn File>
+        })
+    }
+}
+
+/// Create a hard link `new_name` pointing at `old_name`'s inode
+pub fn link_file(old_name: &str, new_name: &str) -> Option<()> {
+    ROOT_INODE.link(old_name, new_name)
+}
+
+/// Remove the `name` entry, freeing the underlying inode once its link count hits 0
+pub fn unlink_file(name: &str) -> Option<()> {
+    ROOT_INODE.unlink(name)
+}
@@ -1,6 +1,8 @@
 //! File trait & inode(dir, file, pipe, stdin, stdout)
 
 mod inode;
+mod pipe;
+mod proc;
 mod stdio;
 
 use crate::mm::UserBuffer;
@@ -15,6 +17,39 @@ pub trait File: Send + Sync {
     fn read(&self, buf: UserBuffer) -> usize;
     /// write to the file from buf, return the number of bytes written
     fn write(&self, buf: UserBuffer) -> usize;
+    /// reposition the file's cursor and return the resulting absolute offset,
+    /// or `-1` if the file does not support seeking (stdin/stdout/pipes)
+    fn seek(&self, _offset: i64, _whence: SeekOrigin) -> isize {
+        -1
+    }
+    /// number of hard links to this file, `1` for anything that isn't backed
+    /// by an `easy-fs` inode (stdin/stdout/pipes/procfs)
+    fn get_nlink(&self) -> u32 {
+        1
+    }
+}
+
+/// Where a `seek` offset is measured from, decoded from a raw POSIX `whence` value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeekOrigin {
+    /// seek from the beginning of the file
+    Start,
+    /// seek from the current position
+    Cur,
+    /// seek from the end of the file
+    End,
+}
+
+impl SeekOrigin {
+    /// decode a raw POSIX `whence` value (0/1/2), `None` if it is none of those
+    pub fn from_whence(whence: u32) -> Option<Self> {
+        match whence {
+            0 => Some(Self::Start),
+            1 => Some(Self::Cur),
+            2 => Some(Self::End),
+            _ => None,
+        }
+    }
 }
 
 /// The stat of a inode
@@ -114,4 +149,6 @@ bitflags! {
 
 use alloc::{string::String, vec::Vec};
 pub use inode::{list_apps, open_file, OSInode, OpenFlags};
+pub use pipe::{make_pipe, Pipe};
+pub use proc::{open_proc_file, ProcInode};
 pub use stdio::{Stdin, Stdout};
@@ -0,0 +1,274 @@
+//! Anonymous pipes: a shared ring buffer plus a read-end/write-end `File` pair
+
+use super::File;
+use crate::mm::UserBuffer;
+use crate::sync::UPSafeCell;
+use crate::task::suspend_current_and_run_next;
+use alloc::sync::{Arc, Weak};
+
+const RING_BUFFER_SIZE: usize = 32;
+
+#[derive(Copy, Clone, PartialEq)]
+enum RingBufferStatus {
+    Full,
+    Empty,
+    Normal,
+}
+
+/// The buffer shared by a pipe's read end and write end
+pub struct PipeRingBuffer {
+    arr: [u8; RING_BUFFER_SIZE],
+    head: usize,
+    tail: usize,
+    status: RingBufferStatus,
+    write_end: Option<Weak<Pipe>>,
+}
+
+impl PipeRingBuffer {
+    pub fn new() -> Self {
+        Self {
+            arr: [0; RING_BUFFER_SIZE],
+            head: 0,
+            tail: 0,
+            status: RingBufferStatus::Empty,
+            write_end: None,
+        }
+    }
+    pub fn set_write_end(&mut self, write_end: &Arc<Pipe>) {
+        self.write_end = Some(Arc::downgrade(write_end));
+    }
+    pub fn write_byte(&mut self, byte: u8) {
+        self.status = RingBufferStatus::Normal;
+        self.arr[self.tail] = byte;
+        self.tail = (self.tail + 1) % RING_BUFFER_SIZE;
+        if self.tail == self.head {
+            self.status = RingBufferStatus::Full;
+        }
+    }
+    pub fn read_byte(&mut self) -> u8 {
+        self.status = RingBufferStatus::Normal;
+        let c = self.arr[self.head];
+        self.head = (self.head + 1) % RING_BUFFER_SIZE;
+        if self.head == self.tail {
+            self.status = RingBufferStatus::Empty;
+        }
+        c
+    }
+    pub fn available_read(&self) -> usize {
+        if self.status == RingBufferStatus::Empty {
+            0
+        } else if self.tail > self.head {
+            self.tail - self.head
+        } else {
+            self.tail + RING_BUFFER_SIZE - self.head
+        }
+    }
+    pub fn available_write(&self) -> usize {
+        if self.status == RingBufferStatus::Full {
+            0
+        } else {
+            RING_BUFFER_SIZE - self.available_read()
+        }
+    }
+    /// whether all write ends of this buffer have been dropped
+    pub fn all_write_ends_closed(&self) -> bool {
+        self.write_end.as_ref().unwrap().upgrade().is_none()
+    }
+}
+
+/// One end of a pipe; readable xor writable
+pub struct Pipe {
+    readable: bool,
+    writable: bool,
+    buffer: Arc<UPSafeCell<PipeRingBuffer>>,
+}
+
+impl Pipe {
+    /// Wrap `buffer` as the pipe's read end
+    pub fn read_end_with_buffer(buffer: Arc<UPSafeCell<PipeRingBuffer>>) -> Self {
+        Self {
+            readable: true,
+            writable: false,
+            buffer,
+        }
+    }
+    /// Wrap `buffer` as the pipe's write end
+    pub fn write_end_with_buffer(buffer: Arc<UPSafeCell<PipeRingBuffer>>) -> Self {
+        Self {
+            readable: false,
+            writable: true,
+            buffer,
+        }
+    }
+}
+
+/// Create a pipe, returning `(read_end, write_end)`
+pub fn make_pipe() -> (Arc<Pipe>, Arc<Pipe>) {
+    let buffer = Arc::new(unsafe { UPSafeCell::new(PipeRingBuffer::new()) });
+    let read_end = Arc::new(Pipe::read_end_with_buffer(buffer.clone()));
+    let write_end = Arc::new(Pipe::write_end_with_buffer(buffer.clone()));
+    buffer.exclusive_access().set_write_end(&write_end);
+    (read_end, write_end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::boxed::Box;
+    use alloc::vec::Vec;
+
+    /// `UserBuffer` holds `&'static mut [u8]`s carved out of physical memory
+    /// by the real page-table walk; a leaked heap allocation is the easiest
+    /// stand-in with the same lifetime for a test.
+    fn leaked_buffer(bytes: &[u8]) -> &'static mut [u8] {
+        Box::leak(bytes.to_vec().into_boxed_slice())
+    }
+
+    #[test]
+    fn starts_empty() {
+        let buf = PipeRingBuffer::new();
+        assert_eq!(buf.available_read(), 0);
+        assert_eq!(buf.available_write(), RING_BUFFER_SIZE);
+    }
+
+    #[test]
+    fn write_then_read_round_trips() {
+        let mut buf = PipeRingBuffer::new();
+        for b in b"hello" {
+            buf.write_byte(*b);
+        }
+        assert_eq!(buf.available_read(), 5);
+        let mut out = Vec::new();
+        for _ in 0..5 {
+            out.push(buf.read_byte());
+        }
+        assert_eq!(&out, b"hello");
+        assert_eq!(buf.available_read(), 0);
+    }
+
+    #[test]
+    fn filling_the_buffer_leaves_no_room_to_write() {
+        let mut buf = PipeRingBuffer::new();
+        for _ in 0..RING_BUFFER_SIZE {
+            buf.write_byte(0);
+        }
+        assert_eq!(buf.available_write(), 0);
+        assert_eq!(buf.available_read(), RING_BUFFER_SIZE);
+    }
+
+    #[test]
+    fn write_end_and_read_end_round_trip_through_the_file_trait() {
+        let (read_end, write_end) = make_pipe();
+        let written = write_end.write(UserBuffer::new(alloc::vec![leaked_buffer(b"hello")]));
+        assert_eq!(written, 5);
+
+        let dst = leaked_buffer(&[0; 5]);
+        let dst_ptr = dst.as_ptr();
+        let read = read_end.read(UserBuffer::new(alloc::vec![dst]));
+        assert_eq!(read, 5);
+        assert_eq!(unsafe { core::slice::from_raw_parts(dst_ptr, 5) }, b"hello");
+    }
+
+    /// A producer/consumer pair that hands over more bytes than the ring
+    /// buffer can hold at once, interleaving `write_end.write` and
+    /// `read_end.read` chunk by chunk the way a reader draining a pipe
+    /// concurrently with a writer would, confirming data survives a
+    /// wraparound when moved exclusively through the `File` trait rather
+    /// than `PipeRingBuffer` directly.
+    ///
+    /// This does not drive a call into the buffer-full/buffer-empty branch
+    /// that calls `suspend_current_and_run_next`, since exercising that
+    /// requires a running task scheduler this test has no access to.
+    #[test]
+    fn producer_consumer_round_trip_spans_more_than_one_buffer() {
+        let (read_end, write_end) = make_pipe();
+        let chunk: Vec<u8> = alloc::vec![0xAB; RING_BUFFER_SIZE / 2];
+        let mut total_read = Vec::new();
+        for _ in 0..4 {
+            let written = write_end.write(UserBuffer::new(alloc::vec![leaked_buffer(&chunk)]));
+            assert_eq!(written, chunk.len());
+            let dst = leaked_buffer(&alloc::vec![0; chunk.len()]);
+            let dst_ptr = dst.as_ptr();
+            let read = read_end.read(UserBuffer::new(alloc::vec![dst]));
+            assert_eq!(read, chunk.len());
+            total_read.extend_from_slice(unsafe { core::slice::from_raw_parts(dst_ptr, chunk.len()) });
+        }
+        assert_eq!(total_read.len(), chunk.len() * 4);
+        assert!(total_read.iter().all(|&b| b == 0xAB));
+    }
+
+    #[test]
+    fn read_end_reports_eof_once_every_write_end_is_dropped() {
+        let (read_end, write_end) = make_pipe();
+        drop(write_end);
+        let dst = leaked_buffer(&[0; 5]);
+        let read = read_end.read(UserBuffer::new(alloc::vec![dst]));
+        assert_eq!(read, 0);
+    }
+}
+
+impl File for Pipe {
+    fn readable(&self) -> bool {
+        self.readable
+    }
+    fn writable(&self) -> bool {
+        self.writable
+    }
+    fn read(&self, buf: UserBuffer) -> usize {
+        assert!(self.readable());
+        let want_to_read = buf.len();
+        let mut buf_iter = buf.into_iter();
+        let mut already_read = 0usize;
+        loop {
+            let mut ring_buffer = self.buffer.exclusive_access();
+            let loop_read = ring_buffer.available_read();
+            if loop_read == 0 {
+                if ring_buffer.all_write_ends_closed() {
+                    return already_read;
+                }
+                drop(ring_buffer);
+                suspend_current_and_run_next();
+                continue;
+            }
+            for _ in 0..loop_read {
+                if let Some(byte_ref) = buf_iter.next() {
+                    unsafe {
+                        *byte_ref = ring_buffer.read_byte();
+                    }
+                    already_read += 1;
+                    if already_read == want_to_read {
+                        return already_read;
+                    }
+                } else {
+                    return already_read;
+                }
+            }
+        }
+    }
+    fn write(&self, buf: UserBuffer) -> usize {
+        assert!(self.writable());
+        let want_to_write = buf.len();
+        let mut buf_iter = buf.into_iter();
+        let mut already_write = 0usize;
+        loop {
+            let mut ring_buffer = self.buffer.exclusive_access();
+            let loop_write = ring_buffer.available_write();
+            if loop_write == 0 {
+                drop(ring_buffer);
+                suspend_current_and_run_next();
+                continue;
+            }
+            for _ in 0..loop_write {
+                if let Some(byte_ref) = buf_iter.next() {
+                    ring_buffer.write_byte(unsafe { *byte_ref });
+                    already_write += 1;
+                    if already_write == want_to_write {
+                        return already_write;
+                    }
+                } else {
+                    return already_write;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,129 @@
+//! A tiny read-only procfs exposing live per-task state under `/proc`.
+//!
+//! Each `/proc/<pid>/*` path is served on the fly straight out of the global
+//! task table: there is no on-disk inode backing it, and nothing under
+//! `/proc` is writable. [`open_proc_file`] is consulted by
+//! [`super::inode::open_file`] before it falls back to the disk filesystem,
+//! so `/proc/...` paths never touch `easy-fs`.
+
+use super::File;
+use crate::mm::UserBuffer;
+use crate::sync::UPSafeCell;
+use crate::task::{get_task_by_pid, TaskStatus};
+use crate::timer::get_time_ms;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+/// Which `/proc/<pid>/*` file a [`ProcInode`] serves
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ProcKind {
+    /// `stat`: `<pid> <status char> <time>`, the status char being R (ready or
+    /// running) or Z (exited) and the time the kernel's current `get_time_ms()`
+    /// reading; the task's own accumulated running time is not tracked or shown
+    Stat,
+    /// `status`: the same fields as `stat`, laid out as `key:\tvalue` lines
+    Status,
+    /// `syscalls`: the non-zero entries of the task's `syscalls` table
+    Syscalls,
+}
+
+/// A synthetic `/proc/<pid>/*` file, generated lazily from live task state
+pub struct ProcInode {
+    pid: usize,
+    kind: ProcKind,
+    /// the rendered content, snapshotted from live task state on the first
+    /// `read` so later calls slice a stable buffer instead of re-rendering
+    /// (and re-timestamping) on every call
+    content: UPSafeCell<Option<Vec<u8>>>,
+    /// byte offset into `content`, advanced as `read` is called, same idiom
+    /// as [`super::inode::OSInode`]'s cursor
+    offset: UPSafeCell<usize>,
+}
+
+impl ProcInode {
+    fn status_char(status: TaskStatus) -> char {
+        match status {
+            TaskStatus::Running | TaskStatus::Ready => 'R',
+            TaskStatus::Exited => 'Z',
+        }
+    }
+
+    /// Serialize the requested view of the task's live state, empty if the
+    /// pid no longer exists
+    fn render(&self) -> Vec<u8> {
+        let Some(task) = get_task_by_pid(self.pid) else {
+            return Vec::new();
+        };
+        let inner = task.inner_exclusive_access();
+        let status = Self::status_char(inner.task_status);
+        let time = get_time_ms();
+        match self.kind {
+            ProcKind::Stat => alloc::format!("{} {} {}\n", self.pid, status, time).into_bytes(),
+            ProcKind::Status => alloc::format!(
+                "Pid:\t{}\nState:\t{}\nTime:\t{} ms\n",
+                self.pid,
+                status,
+                time
+            )
+            .into_bytes(),
+            ProcKind::Syscalls => {
+                let mut out = String::new();
+                for (id, times) in inner.syscalls.iter().enumerate() {
+                    if *times > 0 {
+                        out.push_str(&alloc::format!("{}: {}\n", id, times));
+                    }
+                }
+                out.into_bytes()
+            }
+        }
+    }
+}
+
+impl File for ProcInode {
+    fn readable(&self) -> bool {
+        true
+    }
+    fn writable(&self) -> bool {
+        false
+    }
+    fn read(&self, mut buf: UserBuffer) -> usize {
+        let mut content = self.content.exclusive_access();
+        let data = content.get_or_insert_with(|| self.render());
+        let mut offset = self.offset.exclusive_access();
+        let mut total = 0usize;
+        for slice in buf.buffers.iter_mut() {
+            if *offset >= data.len() {
+                break;
+            }
+            let n = slice.len().min(data.len() - *offset);
+            slice[..n].copy_from_slice(&data[*offset..*offset + n]);
+            *offset += n;
+            total += n;
+        }
+        total
+    }
+    fn write(&self, _buf: UserBuffer) -> usize {
+        0
+    }
+}
+
+/// Parse a `/proc/<pid>/<file>` path into the `ProcInode` it names, or `None`
+/// if `path` is not under `/proc` or names a file we don't serve
+pub fn open_proc_file(path: &str) -> Option<Arc<ProcInode>> {
+    let rest = path.strip_prefix("/proc/")?;
+    let mut parts = rest.splitn(2, '/');
+    let pid: usize = parts.next()?.parse().ok()?;
+    let kind = match parts.next()? {
+        "stat" => ProcKind::Stat,
+        "status" => ProcKind::Status,
+        "syscalls" => ProcKind::Syscalls,
+        _ => return None,
+    };
+    Some(Arc::new(ProcInode {
+        pid,
+        kind,
+        content: unsafe { UPSafeCell::new(None) },
+        offset: unsafe { UPSafeCell::new(0) },
+    }))
+}
@@ -0,0 +1,8 @@
+//! Memory management.
+//!
+//! Re-exports the page-safe user-space copy helpers from [`user_access`]
+//! alongside the rest of this module's address-translation machinery.
+
+mod user_access;
+
+pub use user_access::{read_from_user, write_to_user};
@@ -0,0 +1,75 @@
+//! Page-safe helpers for copying single `Sized` values to/from user space.
+//!
+//! `sys_fstat`, `sys_get_time`, and `sys_task_info` used to each hand-roll the
+//! same unsafe loop over `translated_byte_buffer` fragments to shuttle a
+//! `#[repr(C)]` struct across the user/kernel boundary. [`write_to_user`] and
+//! [`read_from_user`] centralize that loop, copying the object byte by byte
+//! across however many pages it happens to straddle instead of assuming it
+//! fits in one.
+//!
+//! Declared as `mod user_access;` in `mm/mod.rs`, which re-exports
+//! [`write_to_user`] and [`read_from_user`] alongside the rest of the
+//! module's address-translation helpers.
+
+use super::{translated_byte_buffer, PageTable, VirtAddr};
+use core::mem::size_of;
+
+/// Walks `ptr .. ptr + len` page by page and returns `true` iff every page is
+/// mapped in the address space identified by `token`. Mirrors the page walk
+/// `translated_byte_buffer` itself performs, but stops and reports failure
+/// instead of unwrapping an absent translation.
+fn range_is_mapped(token: usize, ptr: *const u8, len: usize) -> bool {
+    let page_table = PageTable::from_token(token);
+    let mut start = ptr as usize;
+    let end = start + len;
+    while start < end {
+        let start_va = VirtAddr::from(start);
+        let mut vpn = start_va.floor();
+        if page_table.translate(vpn).is_none() {
+            return false;
+        }
+        vpn.step();
+        let mut end_va: VirtAddr = vpn.into();
+        end_va = end_va.min(VirtAddr::from(end));
+        start = end_va.into();
+    }
+    true
+}
+
+/// Copy `value` into the user-space object pointed to by `ptr`. Returns
+/// `Err(())` instead of faulting if `ptr .. ptr + size_of::<T>()` is not fully
+/// mapped in the address space identified by `token`.
+pub fn write_to_user<T: Sized>(token: usize, ptr: *mut T, value: &T) -> Result<(), ()> {
+    let len = size_of::<T>();
+    if !range_is_mapped(token, ptr as *const u8, len) {
+        return Err(());
+    }
+    let buffers = translated_byte_buffer(token, ptr as *const u8, len);
+    let mut src = value as *const T as *const u8;
+    unsafe {
+        for buffer in buffers {
+            src.copy_to(buffer.as_mut_ptr(), buffer.len());
+            src = src.add(buffer.len());
+        }
+    }
+    Ok(())
+}
+
+/// Copy the user-space object pointed to by `ptr` into `value`. Returns
+/// `Err(())` instead of faulting if `ptr .. ptr + size_of::<T>()` is not fully
+/// mapped in the address space identified by `token`.
+pub fn read_from_user<T: Sized>(token: usize, ptr: *const T, value: &mut T) -> Result<(), ()> {
+    let len = size_of::<T>();
+    if !range_is_mapped(token, ptr as *const u8, len) {
+        return Err(());
+    }
+    let buffers = translated_byte_buffer(token, ptr as *const u8, len);
+    let mut dst = value as *mut T as *mut u8;
+    unsafe {
+        for buffer in buffers {
+            buffer.as_ptr().copy_to(dst, buffer.len());
+            dst = dst.add(buffer.len());
+        }
+    }
+    Ok(())
+}
@@ -1,8 +1,8 @@
 //! File and filesystem-related syscalls
 
 
-use crate::fs::{link_file, open_file, OpenFlags, Stat, StatMode};
-use crate::mm::{translated_byte_buffer, translated_str, UserBuffer};
+use crate::fs::{link_file, make_pipe, open_file, OpenFlags, SeekOrigin, Stat, StatMode};
+use crate::mm::{translated_byte_buffer, translated_str, write_to_user, UserBuffer};
 use crate::task::{current_task, current_user_token};
 
 pub fn sys_write(fd: usize, buf: *const u8, len: usize) -> isize {
@@ -82,7 +82,7 @@ pub fn sys_close(fd: usize) -> isize {
 
 pub fn sys_fstat(_fd: usize, _st: *mut Stat) -> isize {
     trace!(
-        "kernel:pid[{}] sys_fstat NOT IMPLEMENTED",
+        "kernel:pid[{}] sys_fstat",
         current_task().unwrap().pid.0
     );
     let token = current_user_token();
@@ -99,24 +99,124 @@ pub fn sys_fstat(_fd: usize, _st: *mut Stat) -> isize {
     let nlink = inner.fd_table[_fd].clone().unwrap().get_nlink();
     debug!("get nlink done: {}", nlink);
 
-    
-    let ptr = _st as *const u8;
-    let len = core::mem::size_of::<Stat>();
-    let buffers = translated_byte_buffer(token, ptr, len);
-    
     let stat = Stat::init(StatMode::FILE, nlink);
+    match write_to_user(token, _st, &stat) {
+        Ok(()) => 0,
+        Err(()) => -1,
+    }
+}
 
-    let mut task_ptr = &stat as *const _ as *const u8;
-    unsafe {
-        for buffer in buffers {
-            task_ptr.copy_to(buffer.as_mut_ptr(), buffer.len());
-            task_ptr = task_ptr.add(buffer.len());
-        }
+pub fn sys_pipe(pipe: *mut usize) -> isize {
+    trace!("kernel:pid[{}] sys_pipe", current_task().unwrap().pid.0);
+    let task = current_task().unwrap();
+    let token = current_user_token();
+    let mut inner = task.inner_exclusive_access();
+    let (pipe_read, pipe_write) = make_pipe();
+    let read_fd = inner.alloc_fd();
+    inner.fd_table[read_fd] = Some(pipe_read);
+    let write_fd = inner.alloc_fd();
+    inner.fd_table[write_fd] = Some(pipe_write);
+    drop(inner);
+    if write_to_user(token, pipe, &read_fd).is_err()
+        || write_to_user(token, unsafe { pipe.add(1) }, &write_fd).is_err()
+    {
+        return -1;
     }
-    
     0
 }
 
+pub fn sys_dup(fd: usize) -> isize {
+    trace!("kernel:pid[{}] sys_dup", current_task().unwrap().pid.0);
+    let task = current_task().unwrap();
+    let mut inner = task.inner_exclusive_access();
+    if fd >= inner.fd_table.len() || inner.fd_table[fd].is_none() {
+        return -1;
+    }
+    let file = inner.fd_table[fd].as_ref().unwrap().clone();
+    let new_fd = inner.alloc_fd();
+    inner.fd_table[new_fd] = Some(file);
+    new_fd as isize
+}
+
+/// Hard cap on how large a single task's `fd_table` may grow. Keeps a
+/// user-supplied `newfd`/`arg` (e.g. `dup2(fd, usize::MAX)`) from driving an
+/// unbounded allocation instead of just failing that one syscall.
+const MAX_FD: usize = 128;
+
+pub fn sys_dup2(oldfd: usize, newfd: usize) -> isize {
+    trace!("kernel:pid[{}] sys_dup2", current_task().unwrap().pid.0);
+    let task = current_task().unwrap();
+    let mut inner = task.inner_exclusive_access();
+    if oldfd >= inner.fd_table.len() || inner.fd_table[oldfd].is_none() || newfd >= MAX_FD {
+        return -1;
+    }
+    let file = inner.fd_table[oldfd].as_ref().unwrap().clone();
+    while newfd >= inner.fd_table.len() {
+        inner.fd_table.push(None);
+    }
+    // closes whatever newfd held before by overwriting its slot
+    inner.fd_table[newfd] = Some(file);
+    newfd as isize
+}
+
+/// fcntl: duplicate the fd into the lowest free slot >= arg
+const F_DUPFD: usize = 0;
+/// fcntl: same as `F_DUPFD` (close-on-exec is not modeled, so there is nothing extra to set)
+const F_DUPFD_CLOEXEC: usize = 1030;
+
+pub fn sys_fcntl(fd: usize, cmd: usize, arg: usize) -> isize {
+    trace!("kernel:pid[{}] sys_fcntl", current_task().unwrap().pid.0);
+    let task = current_task().unwrap();
+    let mut inner = task.inner_exclusive_access();
+    if fd >= inner.fd_table.len() || inner.fd_table[fd].is_none() {
+        return -1;
+    }
+    match cmd {
+        F_DUPFD | F_DUPFD_CLOEXEC => {
+            if arg >= MAX_FD {
+                return -1;
+            }
+            let file = inner.fd_table[fd].as_ref().unwrap().clone();
+            let mut new_fd = arg;
+            while new_fd >= inner.fd_table.len() {
+                inner.fd_table.push(None);
+            }
+            while inner.fd_table[new_fd].is_some() {
+                new_fd += 1;
+                if new_fd >= MAX_FD {
+                    return -1;
+                }
+                if new_fd >= inner.fd_table.len() {
+                    inner.fd_table.push(None);
+                }
+            }
+            inner.fd_table[new_fd] = Some(file);
+            new_fd as isize
+        }
+        _ => -1,
+    }
+}
+
+pub fn sys_lseek(fd: usize, offset: i64, whence: u32) -> isize {
+    trace!("kernel:pid[{}] sys_lseek", current_task().unwrap().pid.0);
+    let task = current_task().unwrap();
+    let inner = task.inner_exclusive_access();
+    if fd >= inner.fd_table.len() {
+        return -1;
+    }
+    if let Some(file) = &inner.fd_table[fd] {
+        let file = file.clone();
+        // release current task TCB manually to avoid multi-borrow
+        drop(inner);
+        match SeekOrigin::from_whence(whence) {
+            Some(origin) => file.seek(offset, origin),
+            None => -1,
+        }
+    } else {
+        -1
+    }
+}
+
 /// YOUR JOB: Implement linkat.
 pub fn sys_linkat(_old_name: *const u8, _new_name: *const u8) -> isize {
     trace!(
@@ -0,0 +1,104 @@
+//! Syscall dispatch: decodes a trap's `(syscall_id, args)` pair and runs the
+//! matching `sys_*` implementation in [`fs`] or [`process`].
+
+pub mod fs;
+pub mod process;
+
+use crate::fs::Stat;
+use fs::*;
+use process::*;
+
+/// write buf to fd
+pub const SYSCALL_WRITE: usize = 64;
+/// read from fd
+pub const SYSCALL_READ: usize = 63;
+/// open a file
+pub const SYSCALL_OPEN: usize = 56;
+/// close a fd
+pub const SYSCALL_CLOSE: usize = 57;
+/// get file status
+pub const SYSCALL_FSTAT: usize = 80;
+/// create a pipe
+pub const SYSCALL_PIPE: usize = 59;
+/// duplicate a fd
+pub const SYSCALL_DUP: usize = 23;
+/// duplicate a fd into a specific slot
+pub const SYSCALL_DUP2: usize = 24;
+/// fd control
+pub const SYSCALL_FCNTL: usize = 25;
+/// reposition a fd's cursor
+pub const SYSCALL_LSEEK: usize = 62;
+/// create a hard link
+pub const SYSCALL_LINKAT: usize = 37;
+/// remove a hard link
+pub const SYSCALL_UNLINKAT: usize = 35;
+/// exit current task
+pub const SYSCALL_EXIT: usize = 93;
+/// give up the CPU for this scheduling round
+pub const SYSCALL_YIELD: usize = 124;
+/// get current task's pid
+pub const SYSCALL_GETPID: usize = 172;
+/// fork a child task
+pub const SYSCALL_FORK: usize = 220;
+/// replace the current task's address space
+pub const SYSCALL_EXEC: usize = 221;
+/// wait for a child to exit
+pub const SYSCALL_WAITPID: usize = 260;
+/// get wall-clock time
+pub const SYSCALL_GET_TIME: usize = 169;
+/// get this task's scheduling/syscall-count info
+pub const SYSCALL_TASK_INFO: usize = 410;
+/// map a range of the task's address space
+pub const SYSCALL_MMAP: usize = 222;
+/// unmap a range of the task's address space
+pub const SYSCALL_MUNMAP: usize = 215;
+/// grow or shrink the task's heap
+pub const SYSCALL_SBRK: usize = 214;
+/// fork + exec in one step
+pub const SYSCALL_SPAWN: usize = 400;
+/// install a syscall filter on the current task
+pub const SYSCALL_SECCOMP: usize = 277;
+/// set the current task's stride-scheduling priority
+pub const SYSCALL_SET_PRIORITY: usize = 140;
+
+/// Decode and run one syscall, returning the value to place in `a0`.
+///
+/// Every syscall is checked against the current task's installed
+/// `seccomp_filter` before it runs. On denial, [`enforce_seccomp`] either
+/// kills the task (default action — execution never reaches the `return`
+/// below) or, if the filter was installed with [`process::SECCOMP_RET_ERRNO`],
+/// leaves the task running and we report `-1` in its place.
+pub fn syscall(syscall_id: usize, args: [usize; 6]) -> isize {
+    if !enforce_seccomp(syscall_id) {
+        return -1;
+    }
+    match syscall_id {
+        SYSCALL_WRITE => sys_write(args[0], args[1] as *const u8, args[2]),
+        SYSCALL_READ => sys_read(args[0], args[1] as *const u8, args[2]),
+        SYSCALL_OPEN => sys_open(args[0] as *const u8, args[1] as u32),
+        SYSCALL_CLOSE => sys_close(args[0]),
+        SYSCALL_FSTAT => sys_fstat(args[0], args[1] as *mut Stat),
+        SYSCALL_PIPE => sys_pipe(args[0] as *mut usize),
+        SYSCALL_DUP => sys_dup(args[0]),
+        SYSCALL_DUP2 => sys_dup2(args[0], args[1]),
+        SYSCALL_FCNTL => sys_fcntl(args[0], args[1], args[2]),
+        SYSCALL_LSEEK => sys_lseek(args[0], args[1] as i64, args[2] as u32),
+        SYSCALL_LINKAT => sys_linkat(args[0] as *const u8, args[1] as *const u8),
+        SYSCALL_UNLINKAT => sys_unlinkat(args[0] as *const u8),
+        SYSCALL_EXIT => sys_exit(args[0] as i32),
+        SYSCALL_YIELD => sys_yield(),
+        SYSCALL_GETPID => sys_getpid(),
+        SYSCALL_FORK => sys_fork(),
+        SYSCALL_EXEC => sys_exec(args[0] as *const u8),
+        SYSCALL_WAITPID => sys_waitpid(args[0] as isize, args[1] as *mut i32),
+        SYSCALL_GET_TIME => sys_get_time(args[0] as *mut TimeVal, args[1]),
+        SYSCALL_TASK_INFO => sys_task_info(args[0] as *mut TaskInfo),
+        SYSCALL_MMAP => sys_mmap(args[0], args[1], args[2]),
+        SYSCALL_MUNMAP => sys_munmap(args[0], args[1]),
+        SYSCALL_SBRK => sys_sbrk(args[0] as i32),
+        SYSCALL_SPAWN => sys_spawn(args[0] as *const u8),
+        SYSCALL_SECCOMP => sys_seccomp(args[0], args[1] as *const u32),
+        SYSCALL_SET_PRIORITY => sys_set_priority(args[0] as isize),
+        _ => panic!("Unsupported syscall_id: {}", syscall_id),
+    }
+}
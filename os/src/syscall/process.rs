@@ -4,12 +4,136 @@ use alloc::sync::Arc;
 use crate::{
     config::{MAX_SYSCALL_NUM, TIMEVAL},
     loader::get_app_data_by_name,
-    mm::{translated_byte_buffer, translated_refmut, translated_str, MapPermission, VirtAddr},
+    mm::{read_from_user, translated_refmut, translated_str, write_to_user, MapPermission, VirtAddr},
     task::{
-        add_task, current_task, current_task_is_mapped, current_task_mmap, current_task_unmap, current_user_token, exit_current_and_run_next, suspend_current_and_run_next, TaskStatus
+        add_task, current_task, current_task_is_mapped, current_task_mmap, current_task_set_priority, current_task_unmap, current_user_token, exit_current_and_run_next, suspend_current_and_run_next, TaskStatus
     }, timer::{get_time_ms, get_time_us},
 };
 
+/// `seccomp(SECCOMP_SET_MODE_STRICT, ...)`: only read/write/exit/yield are permitted
+pub const SECCOMP_MODE_STRICT: usize = 1;
+/// `seccomp(SECCOMP_SET_MODE_FILTER, ...)`: permitted syscalls come from a user-supplied bitmap
+pub const SECCOMP_MODE_FILTER: usize = 2;
+/// OR this into `mode` to make a denied syscall return `-1` (`EPERM`) instead
+/// of killing the task — the install's default-action choice
+pub const SECCOMP_RET_ERRNO: usize = 0x100;
+
+/// The syscalls let through by [`SECCOMP_MODE_STRICT`], by syscall number
+const SECCOMP_STRICT_ALLOWED: [usize; 4] = [
+    crate::syscall::SYSCALL_READ,
+    crate::syscall::SYSCALL_WRITE,
+    crate::syscall::SYSCALL_EXIT,
+    crate::syscall::SYSCALL_YIELD,
+];
+
+/// What to do with a syscall a filter denies
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SeccompAction {
+    /// terminate the task with a nonzero exit code (seccomp's default action)
+    Kill,
+    /// skip the syscall and report `-1` (`EPERM`) to the caller instead
+    ReturnError,
+}
+
+/// A task's installed syscall filter. Stored on the TCB, inherited by `fork`
+/// and `spawn`, and preserved across `exec` — mirrors the seccomp inheritance
+/// semantics of Linux's `seccomp(2)`.
+#[derive(Clone)]
+pub enum SeccompFilter {
+    /// no filter installed: every syscall is permitted
+    Disabled,
+    /// only [`SECCOMP_STRICT_ALLOWED`] syscalls are permitted
+    Strict(SeccompAction),
+    /// `bitmap[i] != 0` means syscall number `i` is permitted
+    Filter(alloc::boxed::Box<[u32; MAX_SYSCALL_NUM]>, SeccompAction),
+}
+
+impl SeccompFilter {
+    /// Whether `syscall_id` is allowed under this filter
+    pub fn allows(&self, syscall_id: usize) -> bool {
+        match self {
+            SeccompFilter::Disabled => true,
+            SeccompFilter::Strict(_) => SECCOMP_STRICT_ALLOWED.contains(&syscall_id),
+            SeccompFilter::Filter(bitmap, _) => {
+                syscall_id < MAX_SYSCALL_NUM && bitmap[syscall_id] != 0
+            }
+        }
+    }
+
+    /// What to do when `allows` returns `false`. Meaningless (never
+    /// consulted) on `Disabled`, since that variant always allows.
+    pub fn action(&self) -> SeccompAction {
+        match self {
+            SeccompFilter::Disabled => SeccompAction::Kill,
+            SeccompFilter::Strict(action) | SeccompFilter::Filter(_, action) => *action,
+        }
+    }
+}
+
+#[cfg(test)]
+mod seccomp_tests {
+    use super::*;
+
+    #[test]
+    fn disabled_allows_everything() {
+        let filter = SeccompFilter::Disabled;
+        assert!(filter.allows(crate::syscall::SYSCALL_WRITE));
+        assert!(filter.allows(0xdead));
+    }
+
+    #[test]
+    fn strict_allows_only_the_fixed_set() {
+        let filter = SeccompFilter::Strict(SeccompAction::Kill);
+        for id in SECCOMP_STRICT_ALLOWED {
+            assert!(filter.allows(id));
+        }
+        assert!(!filter.allows(crate::syscall::SYSCALL_OPEN));
+    }
+
+    #[test]
+    fn filter_follows_the_bitmap() {
+        let mut bitmap = alloc::boxed::Box::new([0u32; MAX_SYSCALL_NUM]);
+        bitmap[crate::syscall::SYSCALL_WRITE] = 1;
+        let filter = SeccompFilter::Filter(bitmap, SeccompAction::Kill);
+        assert!(filter.allows(crate::syscall::SYSCALL_WRITE));
+        assert!(!filter.allows(crate::syscall::SYSCALL_READ));
+        assert!(!filter.allows(MAX_SYSCALL_NUM + 1));
+    }
+
+    #[test]
+    fn action_defaults_to_kill_and_honors_return_error() {
+        assert!(SeccompFilter::Strict(SeccompAction::Kill).action() == SeccompAction::Kill);
+        assert!(
+            SeccompFilter::Strict(SeccompAction::ReturnError).action()
+                == SeccompAction::ReturnError
+        );
+    }
+
+    /// `enforce_seccomp` itself needs a live `current_task()`, which only
+    /// exists with a running scheduler; `seccomp_verdict` is the decision it
+    /// delegates to, so driving it directly here exercises the real
+    /// kill-vs-return-error choice a denied syscall gets, including that an
+    /// allowed syscall is left to run.
+    #[test]
+    fn an_allowed_syscall_runs_while_a_denied_one_is_killed_by_default() {
+        let filter = SeccompFilter::Strict(SeccompAction::Kill);
+        assert_eq!(seccomp_verdict(&filter, crate::syscall::SYSCALL_WRITE), None);
+        assert_eq!(
+            seccomp_verdict(&filter, crate::syscall::SYSCALL_OPEN),
+            Some(SeccompAction::Kill)
+        );
+    }
+
+    #[test]
+    fn a_denied_syscall_can_return_an_error_instead_of_killing() {
+        let filter = SeccompFilter::Strict(SeccompAction::ReturnError);
+        assert_eq!(
+            seccomp_verdict(&filter, crate::syscall::SYSCALL_OPEN),
+            Some(SeccompAction::ReturnError)
+        );
+    }
+}
+
 #[repr(C)]
 #[derive(Debug)]
 pub struct TimeVal {
@@ -56,7 +180,7 @@ pub fn sys_fork() -> isize {
     let new_task = current_task.fork();
     let new_pid = new_task.pid.0;
     // modify trap context of new_task, because it returns immediately after switching
-    let trap_cx = new_task.inner_exclusive_access().get_trap_cx();
+    let trap_cx = new_task.get_trap_cx();
     // we do not have to move to next instruction since we have done it before
     // for child process, fork returns 0
     trap_cx.x[10] = 0;
@@ -130,63 +254,41 @@ pub fn sys_waitpid(pid: isize, exit_code_ptr: *mut i32) -> isize {
     // ---- release current PCB automatically
 }
 
-/// HINT: You might reimplement it with virtual memory management.
-/// HINT: What if [`TimeVal`] is splitted by two pages ?
 pub fn sys_get_time(_ts: *mut TimeVal, _tz: usize) -> isize {
     trace!(
-        "kernel:pid[{}] sys_get_time NOT IMPLEMENTED",
+        "kernel:pid[{}] sys_get_time",
         current_task().unwrap().pid.0
     );
     let token = current_user_token();
-    let ptr = _ts as *const u8;
-    let len = core::mem::size_of::<TimeVal>();
-    let buffers = translated_byte_buffer(token, ptr, len);
-
     let us = get_time_us();
     let time_val = TimeVal {
         sec: us / TIMEVAL,
-        usec: us % TIMEVAL
+        usec: us % TIMEVAL,
     };
-
-    let mut time_ptr = &time_val as *const _ as *const u8;
-    unsafe {
-        for buffer in buffers {
-            time_ptr.copy_to(buffer.as_mut_ptr(), buffer.len());
-            time_ptr = time_ptr.add(buffer.len());
-        }
+    match write_to_user(token, _ts, &time_val) {
+        Ok(()) => 0,
+        Err(()) => -1,
     }
-
-    0
 }
 
-/// HINT: You might reimplement it with virtual memory management.
-/// HINT: What if [`TaskInfo`] is splitted by two pages ?
 pub fn sys_task_info(_ti: *mut TaskInfo) -> isize {
     trace!(
-        "kernel:pid[{}] sys_task_info NOT IMPLEMENTED",
+        "kernel:pid[{}] sys_task_info",
         current_task().unwrap().pid.0
     );
     let token = current_user_token();
-    let ptr = _ti as *const u8;
-    let len = core::mem::size_of::<TaskInfo>();
-    let buffers = translated_byte_buffer(token, ptr, len);
-
     let binding = current_task().unwrap();
     let inner = binding.inner_exclusive_access();
     let task_info = TaskInfo {
         status: inner.task_status,
-        syscall_times: inner.syscall_times,
-        time: get_time_ms()
+        syscall_times: inner.syscalls,
+        time: get_time_ms(),
     };
-
-    let mut task_ptr = &task_info as *const _ as *const u8;
-    unsafe {
-        for buffer in buffers {
-            task_ptr.copy_to(buffer.as_mut_ptr(), buffer.len());
-            task_ptr = task_ptr.add(buffer.len());
-        }
+    drop(inner);
+    match write_to_user(token, _ti, &task_info) {
+        Ok(()) => 0,
+        Err(()) => -1,
     }
-    0
 }
 
 pub fn sys_mmap(_start: usize, _len: usize, _port: usize) -> isize {
@@ -276,15 +378,88 @@ pub fn sys_spawn(_path: *const u8) -> isize {
 
 }
 
-// TODO YOUR JOB: Set task priority.
+/// The verdict for `syscall_id` under `filter`: `None` to let it proceed,
+/// `Some(action)` to deny it via `action`. Factored out of `enforce_seccomp`
+/// so the kill-vs-return-error choice can be tested without a live
+/// `current_task()`.
+fn seccomp_verdict(filter: &SeccompFilter, syscall_id: usize) -> Option<SeccompAction> {
+    if filter.allows(syscall_id) {
+        None
+    } else {
+        Some(filter.action())
+    }
+}
+
+/// The hook a trap-handler syscall dispatcher calls before running each
+/// syscall. Returns `true` if `syscall_id` is allowed to proceed. On denial
+/// it returns `false`, having already taken the installed filter's action:
+/// `Kill` terminates the current task with a nonzero exit code (seccomp's
+/// default action) and never returns to the caller; `ReturnError` leaves the
+/// task running so the dispatcher can report `-1` (`EPERM`) instead of
+/// invoking the syscall.
+pub fn enforce_seccomp(syscall_id: usize) -> bool {
+    let task = current_task().unwrap();
+    let filter = task.inner_exclusive_access().seccomp_filter.clone();
+    match seccomp_verdict(&filter, syscall_id) {
+        None => true,
+        Some(SeccompAction::ReturnError) => false,
+        Some(SeccompAction::Kill) => {
+            drop(task);
+            exit_current_and_run_next(-1);
+            false
+        }
+    }
+}
+
+/// Install a syscall filter on the current task, following `seccomp(2)`'s
+/// `SECCOMP_SET_MODE_STRICT` / `SECCOMP_SET_MODE_FILTER` split. `bitmap_ptr`
+/// is read only for `SECCOMP_MODE_FILTER` and must point at `MAX_SYSCALL_NUM`
+/// `u32`s, one per syscall number, non-zero meaning "permitted". OR
+/// [`SECCOMP_RET_ERRNO`] into `mode` to make a denied syscall return `-1`
+/// instead of killing the task.
+///
+/// The dispatcher in the trap handler consults `inner.seccomp_filter` before
+/// running each syscall and, on denial, either kills the task (default) or
+/// returns `-1`, per the install's default-action choice.
+pub fn sys_seccomp(mode: usize, bitmap_ptr: *const u32) -> isize {
+    trace!("kernel:pid[{}] sys_seccomp", current_task().unwrap().pid.0);
+    let task = current_task().unwrap();
+    let action = if mode & SECCOMP_RET_ERRNO != 0 {
+        SeccompAction::ReturnError
+    } else {
+        SeccompAction::Kill
+    };
+    match mode & !SECCOMP_RET_ERRNO {
+        SECCOMP_MODE_STRICT => {
+            task.inner_exclusive_access().seccomp_filter = SeccompFilter::Strict(action);
+            0
+        }
+        SECCOMP_MODE_FILTER => {
+            let token = current_user_token();
+            let mut bitmap = alloc::boxed::Box::new([0u32; MAX_SYSCALL_NUM]);
+            for (i, slot) in bitmap.iter_mut().enumerate() {
+                let ptr = unsafe { bitmap_ptr.add(i) };
+                if read_from_user(token, ptr, slot).is_err() {
+                    return -1;
+                }
+            }
+            task.inner_exclusive_access().seccomp_filter = SeccompFilter::Filter(bitmap, action);
+            0
+        }
+        _ => -1,
+    }
+}
+
+/// Set task priority (stride scheduling), must be >= 2.
 pub fn sys_set_priority(_prio: isize) -> isize {
     trace!(
-        "kernel:pid[{}] sys_set_priority NOT IMPLEMENTED",
+        "kernel:pid[{}] sys_set_priority",
         current_task().unwrap().pid.0
     );
     if _prio < 2 {
         -1
     } else {
+        current_task_set_priority(_prio as usize);
         _prio
     }
 }
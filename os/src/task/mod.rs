@@ -20,6 +20,7 @@ use crate::mm::{frame_alloc, frame_dealloc, MapPermission, PTEFlags, VirtAddr, V
 use crate::sync::UPSafeCell;
 use crate::timer::get_time_ms;
 use crate::trap::TrapContext;
+use alloc::sync::Arc;
 use alloc::vec::Vec;
 use lazy_static::*;
 use switch::__switch;
@@ -45,21 +46,127 @@ pub struct TaskManager {
 
 /// The task manager inner in 'UPSafeCell'
 struct TaskManagerInner {
-    /// task list
-    tasks: Vec<TaskControlBlock>,
+    /// task list. Tasks are `Arc`-wrapped so a handle can be handed out to
+    /// callers (like `/proc`, via [`get_task_by_pid`]) that read task state
+    /// from entirely outside `TaskManager`'s own lock.
+    tasks: Vec<Arc<TaskControlBlock>>,
     /// id of current `Running` task
     current_task: usize,
 }
 
+/// Stride scheduling step size. A task's `pass` (how much its stride grows per
+/// scheduling round) is `BIG_STRIDE / priority`, so higher-priority tasks accrue
+/// stride more slowly and get picked more often.
+pub const BIG_STRIDE: usize = 0x10000;
+
+/// Compare two strides with wrapping arithmetic. Since `pass <= BIG_STRIDE`, the
+/// true difference between any two tasks' strides never exceeds `BIG_STRIDE`,
+/// which is far below `usize::MAX / 2`; that bound is what makes the sign of the
+/// wrapping difference a reliable stand-in for the real (unbounded) comparison.
+fn stride_cmp(a: usize, b: usize) -> core::cmp::Ordering {
+    let diff = a.wrapping_sub(b);
+    if diff == 0 {
+        core::cmp::Ordering::Equal
+    } else if diff < usize::MAX / 2 {
+        core::cmp::Ordering::Greater
+    } else {
+        core::cmp::Ordering::Less
+    }
+}
+
+/// Pick the `Ready` task with the smallest stride, scanning forward from just
+/// after `current`. This is the actual stride-scheduling selection
+/// `find_next_task` runs; it is factored out behind `status_of`/`stride_of`
+/// accessors (rather than taking `&[Arc<TaskControlBlock>]` directly) so it
+/// can be exercised in tests without standing up a whole `TaskManager`, the
+/// same idiom `TaskControlBlockInner::alloc_fd` uses for `alloc_fd_slot`.
+fn pick_next_ready(
+    current: usize,
+    num_app: usize,
+    status_of: impl Fn(usize) -> TaskStatus,
+    stride_of: impl Fn(usize) -> usize,
+) -> Option<usize> {
+    (current + 1..current + num_app + 1)
+        .map(|id| id % num_app)
+        .filter(|id| status_of(*id) == TaskStatus::Ready)
+        .min_by(|a, b| stride_cmp(stride_of(*a), stride_of(*b)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::cmp::Ordering;
+
+    #[test]
+    fn equal_strides_compare_equal() {
+        assert_eq!(stride_cmp(42, 42), Ordering::Equal);
+    }
+
+    #[test]
+    fn smaller_stride_compares_less() {
+        assert_eq!(stride_cmp(10, 20), Ordering::Less);
+        assert_eq!(stride_cmp(20, 10), Ordering::Greater);
+    }
+
+    #[test]
+    fn wraparound_does_not_flip_a_nearby_comparison() {
+        // a has just wrapped past usize::MAX while b hasn't yet: a is still "ahead"
+        let a = 5usize;
+        let b = usize::MAX - 3;
+        assert_eq!(stride_cmp(a, b), Ordering::Greater);
+        assert_eq!(stride_cmp(b, a), Ordering::Less);
+    }
+
+    #[test]
+    fn higher_priority_means_smaller_pass() {
+        let low_priority_pass = BIG_STRIDE / 2;
+        let high_priority_pass = BIG_STRIDE / 16;
+        assert!(high_priority_pass < low_priority_pass);
+    }
+
+    /// Drives `pick_next_ready` itself (the selection `find_next_task` calls
+    /// on the live task table) across many rounds, advancing each picked
+    /// task's stride by `BIG_STRIDE / priority` the way `run_next_task` does,
+    /// all tasks staying `Ready` throughout, and checks the resulting run
+    /// counts land close to the priority ratio.
+    #[test]
+    fn run_counts_track_priority_ratio_across_rounds() {
+        let priorities = [8usize, 2usize];
+        let statuses = [TaskStatus::Ready; 2];
+        let mut strides = [0usize; 2];
+        let mut run_counts = [0usize; 2];
+        let mut current = 0usize;
+        for _ in 0..1000 {
+            let picked = pick_next_ready(
+                current,
+                priorities.len(),
+                |id| statuses[id],
+                |id| strides[id],
+            )
+            .unwrap();
+            run_counts[picked] += 1;
+            strides[picked] = strides[picked].wrapping_add(BIG_STRIDE / priorities[picked]);
+            current = picked;
+        }
+        // priority 8 vs priority 2: a 4x ratio, same as this scheduler's pass formula
+        let ratio = run_counts[0] as f64 / run_counts[1] as f64;
+        assert!(
+            (3.5..=4.5).contains(&ratio),
+            "expected roughly 4x more runs for the priority-8 task, got {:?}",
+            run_counts
+        );
+    }
+}
+
 lazy_static! {
     /// a `TaskManager` global instance through lazy_static!
     pub static ref TASK_MANAGER: TaskManager = {
         println!("init TASK_MANAGER");
         let num_app = get_num_app();
         println!("num_app = {}", num_app);
-        let mut tasks: Vec<TaskControlBlock> = Vec::new();
+        let mut tasks: Vec<Arc<TaskControlBlock>> = Vec::new();
         for i in 0..num_app {
-            tasks.push(TaskControlBlock::new(get_app_data(i), i));
+            tasks.push(Arc::new(TaskControlBlock::new(get_app_data(i), i)));
         }
         TaskManager {
             num_app,
@@ -79,11 +186,13 @@ impl TaskManager {
     /// Generally, the first task in task list is an idle task (we call it zero process later).
     /// But in ch4, we load apps statically, so the first task is a real app.
     fn run_first_task(&self) -> ! {
-        let mut inner = self.inner.exclusive_access();
-        let next_task = &mut inner.tasks[0];
-        next_task.task_status = TaskStatus::Running;
-        next_task.start_time = get_time_ms();
-        let next_task_cx_ptr = &next_task.task_cx as *const TaskContext;
+        let inner = self.inner.exclusive_access();
+        let next_task = &inner.tasks[0];
+        let mut next_task_inner = next_task.inner_exclusive_access();
+        next_task_inner.task_status = TaskStatus::Running;
+        next_task_inner.start_time = get_time_ms();
+        let next_task_cx_ptr = &next_task_inner.task_cx as *const TaskContext;
+        drop(next_task_inner);
         drop(inner);
         let mut _unused = TaskContext::zero_init();
         // before this, we should drop local variables that must be dropped manually
@@ -95,27 +204,35 @@ impl TaskManager {
 
     /// Change the status of current `Running` task into `Ready`.
     fn mark_current_suspended(&self) {
-        let mut inner = self.inner.exclusive_access();
+        let inner = self.inner.exclusive_access();
         let cur = inner.current_task;
-        inner.tasks[cur].task_status = TaskStatus::Ready;
+        inner.tasks[cur].inner_exclusive_access().task_status = TaskStatus::Ready;
     }
 
-    /// Change the status of current `Running` task into `Exited`.
-    fn mark_current_exited(&self) {
-        let mut inner = self.inner.exclusive_access();
+    /// Change the status of current `Running` task into `Exited` and record
+    /// the exit code `sys_waitpid` will later report to the parent.
+    fn mark_current_exited(&self, exit_code: i32) {
+        let inner = self.inner.exclusive_access();
         let cur = inner.current_task;
-        inner.tasks[cur].task_status = TaskStatus::Exited;
+        let mut task_inner = inner.tasks[cur].inner_exclusive_access();
+        task_inner.task_status = TaskStatus::Exited;
+        task_inner.exit_code = exit_code;
     }
 
     /// Find next task to run and return task id.
     ///
-    /// In this case, we only return the first `Ready` task in task list.
+    /// Implements stride scheduling: among all `Ready` tasks, returns the one
+    /// with the smallest stride (ties broken by scan order starting just after
+    /// the current task).
     fn find_next_task(&self) -> Option<usize> {
         let inner = self.inner.exclusive_access();
         let current = inner.current_task;
-        (current + 1..current + self.num_app + 1)
-            .map(|id| id % self.num_app)
-            .find(|id| inner.tasks[*id].task_status == TaskStatus::Ready)
+        pick_next_ready(
+            current,
+            self.num_app,
+            |id| inner.tasks[id].inner_exclusive_access().task_status,
+            |id| inner.tasks[id].inner_exclusive_access().stride,
+        )
     }
 
     /// Get the current 'Running' task's token.
@@ -132,7 +249,7 @@ impl TaskManager {
 
     /// Change the current 'Running' task's program break
     pub fn change_current_program_brk(&self, size: i32) -> Option<usize> {
-        let mut inner = self.inner.exclusive_access();
+        let inner = self.inner.exclusive_access();
         let cur = inner.current_task;
         inner.tasks[cur].change_program_brk(size)
     }
@@ -141,13 +258,23 @@ impl TaskManager {
     /// or there is no `Ready` task and we can exit with all applications completed
     fn run_next_task(&self) {
         if let Some(next) = self.find_next_task() {
-            let mut inner = self.inner.exclusive_access();
+            let inner = self.inner.exclusive_access();
             let current = inner.current_task;
-            inner.tasks[next].task_status = TaskStatus::Running;
-            inner.tasks[next].start_time = get_time_ms();
+
+            let mut next_inner = inner.tasks[next].inner_exclusive_access();
+            // stride scheduling: the task we are about to run advances its stride
+            // by its pass value now, so the next round's comparison sees it fairly
+            next_inner.stride = next_inner.stride.wrapping_add(next_inner.pass);
+            next_inner.task_status = TaskStatus::Running;
+            next_inner.start_time = get_time_ms();
+            let next_task_cx_ptr = &next_inner.task_cx as *const TaskContext;
+            drop(next_inner);
+
+            let mut current_inner = inner.tasks[current].inner_exclusive_access();
+            let current_task_cx_ptr = &mut current_inner.task_cx as *mut TaskContext;
+            drop(current_inner);
+
             inner.current_task = next;
-            let current_task_cx_ptr = &mut inner.tasks[current].task_cx as *mut TaskContext;
-            let next_task_cx_ptr = &inner.tasks[next].task_cx as *const TaskContext;
             drop(inner);
             // before this, we should drop local variables that must be dropped manually
             unsafe {
@@ -159,13 +286,22 @@ impl TaskManager {
         }
     }
 
+    /// Look up a live task by pid, returning a handle usable outside
+    /// `TaskManager`'s own lock. Used by `/proc/<pid>/*` to read a task's
+    /// state without holding up the scheduler.
+    fn task_by_pid(&self, pid: usize) -> Option<Arc<TaskControlBlock>> {
+        let inner = self.inner.exclusive_access();
+        inner.tasks.iter().find(|t| t.getpid() == pid).cloned()
+    }
+
     ///
     fn current_task_mmap(&self, start: usize, num_pages: usize, pteflags: PTEFlags) -> isize {
-        let mut inner = self.inner.exclusive_access();
+        let inner = self.inner.exclusive_access();
         let current = inner.current_task;
+        let mut task_inner = inner.tasks[current].inner_exclusive_access();
         for i in 0..num_pages {
             let page_start = start + i * PAGE_SIZE;
-            if (inner.tasks[current].memory_set.page_table).translate(VirtAddr(page_start).ceil()).is_some() {
+            if (task_inner.memory_set.page_table).translate(VirtAddr(page_start).ceil()).is_some() {
                 debug!("Mapped, {:?}", page_start);
                 return -1;
             }
@@ -175,24 +311,25 @@ impl TaskManager {
                 None => return -1
             };
 
-            (inner.tasks[current].memory_set.page_table).map(VirtPageNum::from(page_start / PAGE_SIZE), frame.ppn,  pteflags);
+            (task_inner.memory_set.page_table).map(VirtPageNum::from(page_start / PAGE_SIZE), frame.ppn,  pteflags);
         }
         0
     }
 
     ///
     fn current_task_unmap(&self, start: usize, num_pages: usize) -> isize {
-        let mut inner = self.inner.exclusive_access();
+        let inner = self.inner.exclusive_access();
         let current = inner.current_task;
+        let mut task_inner = inner.tasks[current].inner_exclusive_access();
         for i in 0..num_pages {
             let page_start = start + i * PAGE_SIZE;
-            if (inner.tasks[current].memory_set.page_table).translate(VirtAddr(page_start).into()).is_none() {
+            if (task_inner.memory_set.page_table).translate(VirtAddr(page_start).into()).is_none() {
                 return -1;
             }
-            
-            frame_dealloc(inner.tasks[current].memory_set.page_table.root_ppn);
 
-            (inner.tasks[current].memory_set.page_table).unmap(VirtPageNum::from(page_start / PAGE_SIZE));
+            frame_dealloc(task_inner.memory_set.page_table.root_ppn);
+
+            (task_inner.memory_set.page_table).unmap(VirtPageNum::from(page_start / PAGE_SIZE));
         }
         0
     }
@@ -200,26 +337,26 @@ impl TaskManager {
     pub fn is_mapped(&self, start_va: VirtAddr, end_va: VirtAddr, mapped: bool) -> bool {
         let inner = self.inner.exclusive_access();
         let current = inner.current_task;
-        inner.tasks[current].memory_set.is_mapped(start_va, end_va, mapped)
+        inner.tasks[current].inner_exclusive_access().memory_set.is_mapped(start_va, end_va, mapped)
     }
     /// Current task mmap
     pub fn current_mmap(&self, start_va: VirtAddr, end_va: VirtAddr, permissions: MapPermission) {
-        let mut inner = self.inner.exclusive_access();
+        let inner = self.inner.exclusive_access();
         let current = inner.current_task;
-        inner.tasks[current].memory_set.insert_framed_area(start_va, end_va, permissions);
+        inner.tasks[current].inner_exclusive_access().memory_set.insert_framed_area(start_va, end_va, permissions);
 
     }
     /// Current task unmmap
     pub fn current_unmap(&self, start_va: VirtAddr, end_va: VirtAddr) {
-        let mut inner = self.inner.exclusive_access();
+        let inner = self.inner.exclusive_access();
         let current = inner.current_task;
-        inner.tasks[current].memory_set.remove_framed_area(start_va, end_va);
+        inner.tasks[current].inner_exclusive_access().memory_set.remove_framed_area(start_va, end_va);
     }
     /// Current task status
     pub fn current_task_status(&self) -> TaskStatus {
         let inner = self.inner.exclusive_access();
         let current = inner.current_task;
-        let status = inner.tasks[current].task_status;
+        let status = inner.tasks[current].inner_exclusive_access().task_status;
         drop(inner);
         status
     }
@@ -227,25 +364,35 @@ impl TaskManager {
     pub fn current_task_syscalls(&self) -> [u32; MAX_SYSCALL_NUM] {
         let inner = self.inner.exclusive_access();
         let current = inner.current_task;
-        let syscalls = inner.tasks[current].syscalls;
+        let syscalls = inner.tasks[current].inner_exclusive_access().syscalls;
         drop(inner);
         syscalls
     }
     /// Current task syscalls increase
     pub fn current_task_syscalls_increase(&self, syscall_id: usize) {
-        let mut inner = self.inner.exclusive_access();
+        let inner = self.inner.exclusive_access();
         let current = inner.current_task;
-        inner.tasks[current].syscalls[syscall_id] += 1;
+        inner.tasks[current].inner_exclusive_access().syscalls[syscall_id] += 1;
         drop(inner);
     }
     /// Current task cost time
     pub fn current_task_cost_time(&self) -> usize {
         let inner = self.inner.exclusive_access();
         let current = inner.current_task;
-        let time = get_time_ms() - inner.tasks[current].start_time;
+        let start_time = inner.tasks[current].inner_exclusive_access().start_time;
+        let time = get_time_ms() - start_time;
         drop(inner);
         time
     }
+    /// Set the current `Running` task's priority, recomputing its stride pass
+    /// (`pass = BIG_STRIDE / priority`). Caller must ensure `priority >= 2`.
+    pub fn set_current_priority(&self, priority: usize) {
+        let inner = self.inner.exclusive_access();
+        let cur = inner.current_task;
+        let mut cur_inner = inner.tasks[cur].inner_exclusive_access();
+        cur_inner.priority = priority;
+        cur_inner.pass = BIG_STRIDE / priority;
+    }
 }
 /// Run the first task in task list.
 pub fn run_first_task() {
@@ -264,8 +411,8 @@ fn mark_current_suspended() {
 }
 
 /// Change the status of current `Running` task into `Exited`.
-fn mark_current_exited() {
-    TASK_MANAGER.mark_current_exited();
+fn mark_current_exited(exit_code: i32) {
+    TASK_MANAGER.mark_current_exited(exit_code);
 }
 
 /// Suspend the current 'Running' task and run the next task in task list.
@@ -275,8 +422,8 @@ pub fn suspend_current_and_run_next() {
 }
 
 /// Exit the current 'Running' task and run the next task in task list.
-pub fn exit_current_and_run_next() {
-    mark_current_exited();
+pub fn exit_current_and_run_next(exit_code: i32) {
+    mark_current_exited(exit_code);
     run_next_task();
 }
 
@@ -295,6 +442,14 @@ pub fn change_program_brk(size: i32) -> Option<usize> {
     TASK_MANAGER.change_current_program_brk(size)
 }
 
+/// Look up a task by pid in `TASK_MANAGER`'s task table. No task is ever
+/// removed from that table, so an exited or reaped task is still returned;
+/// `None` means the pid is not in the table. Used by `/proc/<pid>/*` to read
+/// a task's state from outside the scheduler.
+pub fn get_task_by_pid(pid: usize) -> Option<Arc<TaskControlBlock>> {
+    TASK_MANAGER.task_by_pid(pid)
+}
+
 /// test
 pub fn current_task_mmap(start: usize, num_pages: usize, pteflags: PTEFlags) -> isize {
     TASK_MANAGER.current_task_mmap(start, num_pages, pteflags)
@@ -338,4 +493,9 @@ pub fn current_task_syscalls_increase(syscall_id: usize) {
 /// Current task cost time
 pub fn current_task_cost_time() -> usize {
     TASK_MANAGER.current_task_cost_time()
-}
\ No newline at end of file
+}
+
+/// Set the current 'Running' task's priority and recompute its stride pass
+pub fn current_task_set_priority(priority: usize) {
+    TASK_MANAGER.set_current_priority(priority);
+}
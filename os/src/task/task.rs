@@ -0,0 +1,283 @@
+//! Task control block.
+//!
+//! Every mutable field lives behind `inner`'s own lock, accessed via
+//! [`TaskControlBlock::inner_exclusive_access`]. The scheduler's own fields
+//! (`task_cx` and the stride-scheduling fields) used to sit directly on
+//! `TaskControlBlock`, relying on `TaskManager`'s lock to protect them instead
+//! — but `get_task_by_pid` hands a `TaskControlBlock` out to callers (like
+//! `/proc`) that run entirely outside `TaskManager`'s lock, so those fields
+//! need their own lock too.
+
+use super::context::TaskContext;
+use super::BIG_STRIDE;
+use crate::config::MAX_SYSCALL_NUM;
+use crate::fs::{File, Stdin, Stdout};
+use crate::mm::MemorySet;
+use crate::sync::UPSafeCell;
+use crate::syscall::process::SeccompFilter;
+use crate::trap::TrapContext;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cell::RefMut;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+/// Priority every static app task starts at; forked/spawned tasks inherit
+/// their parent's priority instead
+const DEFAULT_PRIORITY: usize = 16;
+
+/// A task's pid
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PidHandle(pub usize);
+
+/// Hands out pids for tasks created outside the static app list (`fork`,
+/// `spawn`). Static apps keep their app-list index as their pid, so this
+/// starts well above any plausible app count.
+static NEXT_DYNAMIC_PID: AtomicUsize = AtomicUsize::new(1 << 16);
+
+fn alloc_pid() -> PidHandle {
+    PidHandle(NEXT_DYNAMIC_PID.fetch_add(1, Ordering::Relaxed))
+}
+
+/// A task's position in its life cycle
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum TaskStatus {
+    /// waiting to be scheduled
+    Ready,
+    /// currently on the CPU
+    Running,
+    /// finished; kept around until its parent collects its exit code
+    Exited,
+}
+
+/// Per-task state. `pid` is set once at creation and never changes, so it
+/// stays outside `inner`; everything else — including the scheduler's own
+/// bookkeeping — is reached through [`TaskControlBlock::inner_exclusive_access`].
+pub struct TaskControlBlock {
+    /// the task's pid
+    pub pid: PidHandle,
+    inner: UPSafeCell<TaskControlBlockInner>,
+}
+
+/// The part of a task's state reached through an `Arc<TaskControlBlock>`
+/// rather than through the scheduler's own lock.
+pub struct TaskControlBlockInner {
+    /// wall-clock ms timestamp of when the task was last scheduled in
+    pub start_time: usize,
+    /// saved registers for `__switch`
+    pub task_cx: TaskContext,
+    /// per-syscall invocation counts, bumped by the trap dispatcher before
+    /// running each syscall. The single source of truth for both
+    /// `sys_task_info` and `/proc/<pid>/syscalls`.
+    pub syscalls: [u32; MAX_SYSCALL_NUM],
+    /// stride-scheduling priority, always `>= 2`
+    pub priority: usize,
+    /// stride-scheduling accumulator, advanced by `pass` each time this task runs
+    pub stride: usize,
+    /// stride-scheduling step size, `BIG_STRIDE / priority`
+    pub pass: usize,
+    /// the task's address space, replaced wholesale by `exec`
+    pub memory_set: MemorySet,
+    /// where the task is in its life cycle
+    pub task_status: TaskStatus,
+    /// open file descriptors
+    pub fd_table: Vec<Option<Arc<dyn File>>>,
+    /// live children, removed once reaped by `waitpid`
+    pub children: Vec<Arc<TaskControlBlock>>,
+    /// exit code reported to `waitpid`, valid once the task has exited
+    pub exit_code: i32,
+    /// the installed syscall filter. `fork`/`spawn` clone this into the
+    /// child so filters are inherited; `exec` mutates this same TCB in place
+    /// rather than creating a new one, so the filter naturally survives it.
+    pub seccomp_filter: SeccompFilter,
+}
+
+fn default_fd_table() -> Vec<Option<Arc<dyn File>>> {
+    vec![
+        Some(Arc::new(Stdin) as Arc<dyn File>),
+        Some(Arc::new(Stdout) as Arc<dyn File>),
+        Some(Arc::new(Stdout) as Arc<dyn File>),
+    ]
+}
+
+impl TaskControlBlock {
+    /// Build the initial, `Ready` task for static app `id`, with a default
+    /// stride-scheduling priority of [`DEFAULT_PRIORITY`] (so `pass` is never
+    /// computed from a zero priority).
+    pub fn new(elf_data: &[u8], id: usize) -> Self {
+        let (memory_set, user_sp, entry_point) = MemorySet::from_elf(elf_data);
+        let task_cx = TaskContext::goto_restore(memory_set.trap_cx_user_va());
+        let trap_cx = memory_set.trap_cx();
+        *trap_cx = TrapContext::app_init_context(entry_point, user_sp);
+        Self {
+            pid: PidHandle(id),
+            inner: unsafe {
+                UPSafeCell::new(TaskControlBlockInner {
+                    start_time: 0,
+                    task_cx,
+                    syscalls: [0; MAX_SYSCALL_NUM],
+                    priority: DEFAULT_PRIORITY,
+                    stride: 0,
+                    pass: BIG_STRIDE / DEFAULT_PRIORITY,
+                    memory_set,
+                    task_status: TaskStatus::Ready,
+                    fd_table: default_fd_table(),
+                    children: Vec::new(),
+                    exit_code: 0,
+                    seccomp_filter: SeccompFilter::Disabled,
+                })
+            },
+        }
+    }
+
+    /// Exclusive access to the fields behind this task's own lock
+    pub fn inner_exclusive_access(&self) -> RefMut<'_, TaskControlBlockInner> {
+        self.inner.exclusive_access()
+    }
+
+    /// The task's pid as a plain number
+    pub fn getpid(&self) -> usize {
+        self.pid.0
+    }
+
+    /// This task's page table token
+    pub fn get_user_token(&self) -> usize {
+        self.inner_exclusive_access().memory_set.token()
+    }
+
+    /// This task's trap context
+    pub fn get_trap_cx(&self) -> &'static mut TrapContext {
+        self.inner_exclusive_access().memory_set.trap_cx()
+    }
+
+    /// Grow or shrink the task's heap by `size` bytes, returning the old
+    /// program break, or `None` if the request doesn't fit
+    pub fn change_program_brk(&self, size: i32) -> Option<usize> {
+        self.inner_exclusive_access().memory_set.change_program_brk(size)
+    }
+
+    /// Duplicate this task's address space and fd table into a fresh child,
+    /// inheriting priority and the installed `seccomp_filter` (filters are
+    /// inherited across `fork`, same as Linux).
+    pub fn fork(self: &Arc<Self>) -> Arc<Self> {
+        let mut parent_inner = self.inner_exclusive_access();
+        let memory_set = MemorySet::from_existing_user(&parent_inner.memory_set);
+        let priority = parent_inner.priority;
+        let pass = parent_inner.pass;
+        let child = Arc::new(Self {
+            pid: alloc_pid(),
+            inner: unsafe {
+                UPSafeCell::new(TaskControlBlockInner {
+                    start_time: 0,
+                    task_cx: TaskContext::goto_trap_return(),
+                    syscalls: [0; MAX_SYSCALL_NUM],
+                    priority,
+                    stride: 0,
+                    pass,
+                    memory_set,
+                    task_status: TaskStatus::Ready,
+                    fd_table: parent_inner.fd_table.clone(),
+                    children: Vec::new(),
+                    exit_code: 0,
+                    seccomp_filter: parent_inner.seccomp_filter.clone(),
+                })
+            },
+        });
+        parent_inner.children.push(child.clone());
+        drop(parent_inner);
+        child
+    }
+
+    /// Like [`fork`](Self::fork), but builds the child's address space from
+    /// `elf_data` instead of copying the parent's, so `spawn` does not pay
+    /// for a copy that `exec` would immediately throw away. Returns the child
+    /// and its pid.
+    pub fn fork_without_copy(self: &Arc<Self>, elf_data: &[u8]) -> (Arc<Self>, usize) {
+        let (memory_set, user_sp, entry_point) = MemorySet::from_elf(elf_data);
+        let trap_cx = memory_set.trap_cx();
+        *trap_cx = TrapContext::app_init_context(entry_point, user_sp);
+        let task_cx = TaskContext::goto_restore(memory_set.trap_cx_user_va());
+        let mut parent_inner = self.inner_exclusive_access();
+        let priority = parent_inner.priority;
+        let pass = parent_inner.pass;
+        let child = Arc::new(Self {
+            pid: alloc_pid(),
+            inner: unsafe {
+                UPSafeCell::new(TaskControlBlockInner {
+                    start_time: 0,
+                    task_cx,
+                    syscalls: [0; MAX_SYSCALL_NUM],
+                    priority,
+                    stride: 0,
+                    pass,
+                    memory_set,
+                    task_status: TaskStatus::Ready,
+                    fd_table: parent_inner.fd_table.clone(),
+                    children: Vec::new(),
+                    exit_code: 0,
+                    seccomp_filter: parent_inner.seccomp_filter.clone(),
+                })
+            },
+        });
+        let pid = child.getpid();
+        parent_inner.children.push(child.clone());
+        drop(parent_inner);
+        (child, pid)
+    }
+
+    /// Replace this task's address space in place with `elf_data`'s, keeping
+    /// its pid, fd table, children and `seccomp_filter`.
+    pub fn exec(&self, elf_data: &[u8]) {
+        let (memory_set, user_sp, entry_point) = MemorySet::from_elf(elf_data);
+        let trap_cx = memory_set.trap_cx();
+        *trap_cx = TrapContext::app_init_context(entry_point, user_sp);
+        self.inner_exclusive_access().memory_set = memory_set;
+    }
+}
+
+impl TaskControlBlockInner {
+    /// Allocate the lowest free fd slot, growing the table if needed
+    pub fn alloc_fd(&mut self) -> usize {
+        alloc_fd_slot(&mut self.fd_table)
+    }
+
+    /// Whether this task has exited and is waiting to be reaped
+    pub fn is_zombie(&self) -> bool {
+        self.task_status == TaskStatus::Exited
+    }
+}
+
+/// Find the lowest free slot in `fd_table`, growing it by one `None` slot if
+/// every existing entry is in use. Factored out of [`TaskControlBlockInner`]
+/// so the slot reuse behind `alloc_fd` (used by `sys_open`, `sys_pipe` and
+/// `sys_dup`) can be tested without standing up a whole task.
+fn alloc_fd_slot(fd_table: &mut Vec<Option<Arc<dyn File>>>) -> usize {
+    if let Some(fd) = (0..fd_table.len()).find(|fd| fd_table[*fd].is_none()) {
+        fd
+    } else {
+        fd_table.push(None);
+        fd_table.len() - 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reuses_lowest_free_slot() {
+        let mut fd_table: Vec<Option<Arc<dyn File>>> = vec![Some(dummy_file()), None, Some(dummy_file())];
+        assert_eq!(alloc_fd_slot(&mut fd_table), 1);
+    }
+
+    #[test]
+    fn grows_table_when_full() {
+        let mut fd_table: Vec<Option<Arc<dyn File>>> = vec![Some(dummy_file()), Some(dummy_file())];
+        assert_eq!(alloc_fd_slot(&mut fd_table), 2);
+        assert_eq!(fd_table.len(), 3);
+    }
+
+    fn dummy_file() -> Arc<dyn File> {
+        Arc::new(Stdin)
+    }
+}